@@ -0,0 +1,70 @@
+use crate::credentials;
+use crate::manifest::RepoEntry;
+use crate::progress::ChildProgress;
+use crate::report::{Error, Outcome};
+use gix::NestedProgress;
+use std::path::Path;
+
+/// Clones `entry` into `target` using gix's clone pipeline: prepare the
+/// clone, fetch and (unless the entry is `bare`) check out the resulting
+/// worktree. A `branch` pins the ref to fetch and check out; a `revision`
+/// additionally detaches HEAD to that commit once checked out. `progress`
+/// gets its own nested line for the checkout phase, since `fetch_then_checkout`
+/// and `main_worktree` each take ownership of the line they report against;
+/// `credentials` authenticates the fetch against private remotes.
+pub fn clone_repo(
+    entry: &RepoEntry,
+    target: &Path,
+    mut progress: ChildProgress,
+    credentials: &credentials::Options,
+) -> Result<Outcome, Error> {
+    let mut prepare =
+        gix::prepare_clone(entry.url.as_str(), target).map_err(|e| Error::Other(format!("clone setup failed: {e}")))?;
+
+    if let Some(branch) = &entry.branch {
+        prepare = prepare
+            .with_ref_name(Some(branch.as_str()))
+            .map_err(|e| Error::Other(format!("invalid branch name {branch}: {e}")))?;
+    }
+
+    let mut prepare = credentials.configure_clone(prepare);
+    let checkout_progress = progress.add_child("checkout");
+
+    let (mut checkout, fetch_outcome) = prepare
+        .fetch_then_checkout(progress, &gix::interrupt::IS_INTERRUPTED)
+        .map_err(|e| Error::FetchFailed(e.to_string()))?;
+    let objects = fetch_outcome.ref_map.remote_refs.len();
+
+    if entry.bare {
+        // Dropping an unfinished `PrepareCheckout` deletes the clone; keep it.
+        checkout.persist();
+        return Ok(Outcome::Cloned { objects });
+    }
+
+    let (repo, _) = checkout
+        .main_worktree(checkout_progress, &gix::interrupt::IS_INTERRUPTED)
+        .map_err(|e| Error::Other(format!("checkout failed: {e}")))?;
+
+    if let Some(revision) = &entry.revision {
+        checkout_revision(&repo, revision)
+            .map_err(|e| Error::Other(format!("failed to check out revision {revision}: {e}")))?;
+    }
+
+    Ok(Outcome::Cloned { objects })
+}
+
+/// Detaches `repo`'s HEAD to `revision`, leaving the index and worktree
+/// matching that commit.
+fn checkout_revision(repo: &gix::Repository, revision: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let commit = repo.rev_parse_single(revision)?.detach();
+    repo.edit_reference(gix::refs::transaction::RefEdit {
+        change: gix::refs::transaction::Change::Update {
+            log: Default::default(),
+            expected: gix::refs::transaction::PreviousValue::Any,
+            new: gix::refs::Target::Object(commit),
+        },
+        name: "HEAD".try_into()?,
+        deref: false,
+    })?;
+    crate::worktree::checkout_commit(repo, commit)
+}
@@ -0,0 +1,93 @@
+/// How to authenticate against a remote, derived from `--no-prompt` and
+/// `--token-env`.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// fail fast instead of blocking on an interactive credential prompt
+    pub no_prompt: bool,
+    /// name of an env var holding a token to use for HTTPS remotes
+    pub token_env: Option<String>,
+}
+
+impl Options {
+    fn token(&self) -> Option<String> {
+        self.token_env.as_ref().and_then(|var| std::env::var(var).ok())
+    }
+
+    /// Installs our credential handling on `connection`: a token read from
+    /// `--token-env` wins for HTTPS remotes; otherwise this defers to the
+    /// repo's configured credential helper chain (and the SSH agent for
+    /// `ssh://` remotes) exactly as `git credential` would, unless
+    /// `--no-prompt` asks to fail fast instead of blocking on one.
+    pub fn configure<'remote, 'auth, 'repo, T>(
+        &self,
+        mut connection: gix::remote::Connection<'remote, 'auth, 'repo, T>,
+    ) -> gix::remote::Connection<'remote, 'auth, 'repo, T>
+    where
+        T: gix::protocol::transport::client::blocking_io::Transport,
+    {
+        let repo = connection.remote().repo().clone();
+        connection.set_credentials(self.handler(repo));
+        connection
+    }
+
+    /// Same as `configure`, for the connection `gix::prepare_clone`'s
+    /// `PrepareFetch` establishes internally. There's no builder hook to
+    /// reach into that connection directly, so we piggyback on its
+    /// `configure_connection` callback, which runs right before it's used.
+    pub fn configure_clone(&self, prepare: gix::clone::PrepareFetch) -> gix::clone::PrepareFetch {
+        let this = self.clone();
+        prepare.configure_connection(move |connection| {
+            let repo = connection.remote().repo().clone();
+            connection.set_credentials(this.handler(repo));
+            Ok(())
+        })
+    }
+
+    /// Builds the callback shared by `configure` and `configure_clone`: a
+    /// token from `--token-env` wins for HTTPS remotes; otherwise `repo`'s
+    /// configured credential helper chain is asked, with its interactive
+    /// prompt fallback disabled when `--no-prompt` is set.
+    #[allow(clippy::result_large_err, reason = "matches gix_credentials::protocol::Result itself")]
+    fn handler(
+        &self,
+        repo: gix::Repository,
+    ) -> impl FnMut(
+        gix::credentials::helper::Action,
+    ) -> Result<Option<gix::credentials::protocol::Outcome>, gix::credentials::protocol::Error>
+           + 'static {
+        let no_prompt = self.no_prompt;
+        let token = self.token();
+
+        move |action| {
+            if let gix::credentials::helper::Action::Get(ctx) = &action {
+                if let Some(token) = &token {
+                    if ctx.protocol.as_deref() == Some("https") {
+                        return Ok(Some(gix::credentials::protocol::Outcome {
+                            identity: gix::sec::identity::Account {
+                                username: "token".into(),
+                                password: token.clone(),
+                                oauth_refresh_token: None,
+                            },
+                            next: ctx.clone().into(),
+                        }));
+                    }
+                }
+            }
+
+            let url = action
+                .context()
+                .and_then(|ctx| ctx.url.clone().or_else(|| ctx.to_url()))
+                .ok_or(gix::credentials::protocol::Error::UrlMissing)?;
+            let (mut cascade, _, mut prompt_opts) = repo
+                .config_snapshot()
+                .credential_helpers(gix::url::parse(&url)?)
+                .map_err(|source| gix::credentials::protocol::Error::ConfigureCredentialHelpers {
+                    source: Box::new(source),
+                })?;
+            if no_prompt {
+                prompt_opts.mode = gix::prompt::Mode::Disable;
+            }
+            cascade.invoke(action, prompt_opts)
+        }
+    }
+}
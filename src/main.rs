@@ -1,91 +1,183 @@
+mod clone;
+mod credentials;
+mod manifest;
+mod ops;
+mod progress;
+mod report;
+mod worktree;
+
 use clap::Parser;
+use progress::Reporter;
 use rayon::prelude::*;
 use rayon::ThreadPoolBuilder;
+use report::{Format, Record};
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
+use std::process::ExitCode;
 
 #[derive(Debug, Parser)]
 #[command(about, author, version)]
 struct Args {
     /// runs in serial mode, by default cli runs in parallel mode
-    #[arg(short = 's', long, default_value_t = false)]
+    #[arg(short = 's', long, default_value_t = false, global = true)]
     serial: bool,
     /// max number of jobs to run in parallel, by default num of cpus
-    #[arg(short = 'j', long, default_value_t = 0)]
+    #[arg(short = 'j', long, default_value_t = 0, global = true)]
     jobs: u16,
     /// depth of subdirectories to search for git repos
-    #[arg(short = 'd', long, default_value_t = 1)]
+    #[arg(short = 'd', long, default_value_t = 1, global = true)]
     depth: u8,
+    /// path to a TOML manifest of repos to provision; missing ones are
+    /// cloned, existing ones fall through to the selected operation
+    #[arg(short = 'm', long, global = true)]
+    manifest: Option<PathBuf>,
+    /// fail fast instead of blocking on an interactive credential prompt
+    #[arg(long, default_value_t = false, global = true)]
+    no_prompt: bool,
+    /// name of an env var holding a token to authenticate HTTPS remotes with
+    #[arg(long, global = true)]
+    token_env: Option<String>,
+    /// output format for the end-of-run report
+    #[arg(short = 'f', long, value_enum, default_value = "text", global = true)]
+    format: Format,
     /// path to directory to search for git repos, by default current working directory
+    #[arg(global = true)]
     path: Option<PathBuf>,
+    /// operation to run against each repo, by default `fetch`
+    #[command(subcommand)]
+    command: Option<ops::Command>,
+}
+
+/// Returns `true` if `path` looks like the root of a git repository, either
+/// because it has a `.git` directory/file or because `gix` can open it.
+fn is_git_repo(path: &Path) -> bool {
+    if path.join(".git").exists() {
+        return true;
+    }
+    gix::open(path).is_ok()
 }
 
-fn list_subdirectories<P: AsRef<Path>>(path: P) -> Result<Vec<String>, std::io::Error> {
-    let mut directories = Vec::new();
+/// Walks `base_path` breadth-first, descending at most `depth` levels, and
+/// collects every directory recognized as a git repo along the way. Once a
+/// directory is recognized as a repo it is not descended into further, so
+/// repos nested inside another repo's worktree are not reported.
+///
+/// A directory that can't be read (permission denied, a broken symlink, a
+/// race with deletion) is skipped with a warning rather than aborting the
+/// whole walk, so repos already found at shallower depths are still
+/// returned.
+fn discover_repos<P: AsRef<Path>>(base_path: P, depth: u8) -> Vec<PathBuf> {
+    let base_path = base_path.as_ref();
+    let mut repos = Vec::new();
+    let mut frontier = vec![base_path.to_path_buf()];
 
-    for entry in fs::read_dir(path)? {
-        let entry = entry?;
-        let path = entry.path();
+    for _ in 0..depth {
+        let mut next_frontier = Vec::new();
 
-        if path.is_dir() {
-            if let Some(name) = path.file_name() {
-                directories.push(name.to_string_lossy().to_string());
+        for dir in frontier {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    eprintln!("warning: skipping {}: {}", dir.display(), e);
+                    continue;
+                }
+            };
+
+            for entry in entries {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        eprintln!("warning: skipping an entry of {}: {}", dir.display(), e);
+                        continue;
+                    }
+                };
+                let path = entry.path();
+
+                if !path.is_dir() {
+                    continue;
+                }
+
+                if is_git_repo(&path) {
+                    repos.push(path);
+                } else {
+                    next_frontier.push(path);
+                }
             }
         }
+
+        frontier = next_frontier;
     }
 
-    Ok(directories)
+    repos
 }
 
-fn fetch_repo<P: AsRef<Path>>(path: P, dir: &String) {
-    let repo = match gix::open(path.as_ref()) {
-        Ok(repo) => repo,
+/// Renders `path` relative to `base_path` for use as a display label,
+/// falling back to the full path if it isn't a prefix of `path`.
+fn display_label(base_path: &Path, path: &Path) -> String {
+    path.strip_prefix(base_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Provisions every repo in `manifest_path`'s manifest under `base_path`:
+/// entries missing on disk are cloned, entries that already contain a repo
+/// fall through to `command`. Runs in parallel unless `serial` is set.
+fn provision_from_manifest(
+    base_path: &Path,
+    manifest_path: &Path,
+    command: &ops::Command,
+    credentials: &credentials::Options,
+    serial: bool,
+    format: Format,
+) -> Vec<Record> {
+    let manifest = match manifest::load(manifest_path) {
+        Ok(manifest) => manifest,
         Err(e) => {
-            eprintln!("{}, 错误：目录非git仓库.{}", dir, e);
-            return;
-        }
-    };
-    let remote_result = match repo.find_default_remote(gix::remote::Direction::Fetch) {
-        Some(remote) => remote,
-        None => {
-            eprintln!("{}, 错误：未配置remote", dir);
-            return;
+            eprintln!("error: failed to read manifest {}: {}", manifest_path.display(), e);
+            return Vec::new();
         }
     };
-    let remote = match remote_result {
-        Ok(remote) => remote,
-        Err(e) => {
-            eprintln!("{}, 错误：remote获取失败, {}", dir, e);
-            return;
-        }
+
+    let reporter = Reporter::new(manifest.repos.len(), format);
+
+    let provision_one = |entry: &manifest::RepoEntry| {
+        let target = entry.target_dir(base_path);
+        let label = display_label(base_path, &target);
+        let child = reporter.child(label.clone());
+
+        let result = if is_git_repo(&target) {
+            command.run(&target, child, credentials)
+        } else {
+            clone::clone_repo(entry, &target, child, credentials)
+        };
+        reporter.repo_done();
+        Record::new(label, result)
     };
-    let p = gix::progress::Discard;
-    let outcome_result = remote
-        .connect(gix::remote::Direction::Fetch)
-        .unwrap()
-        .prepare_fetch(
-            &mut gix::progress::Discard,
-            gix::remote::ref_map::Options::default(),
-        )
-        .unwrap()
-        .receive(p, &gix::interrupt::IS_INTERRUPTED);
-    match outcome_result {
-        Ok(outcome) => {
-            println!(
-                "{}: 拉取成功完成! 接收到 {} 个对象",
-                dir,
-                outcome.ref_map.remote_refs.len()
-            );
-        }
-        Err(e) => {
-            eprintln!("{}: 拉取失败: {}", dir, e);
-        }
+
+    if serial {
+        manifest.repos.iter().map(provision_one).collect()
+    } else {
+        manifest.repos.par_iter().map(provision_one).collect()
     }
 }
-fn main() {
+
+fn main() -> ExitCode {
+    // SAFETY: `init_handler` requires its `interrupt` callback to be safe to
+    // run from a signal handler (async-signal-safe: no heap allocation, no
+    // locking). Ours is a no-op closure, so that's trivially satisfied.
+    unsafe {
+        gix::interrupt::init_handler(1, || {}).expect("interrupt handler installed exactly once");
+    }
+
     let args = Args::parse();
     let base_path = args.path.unwrap_or_else(|| ".".into());
+    let command = args.command.unwrap_or(ops::Command::Fetch);
+    let credentials = credentials::Options {
+        no_prompt: args.no_prompt,
+        token_env: args.token_env,
+    };
 
     if args.jobs > 0 {
         ThreadPoolBuilder::new()
@@ -94,22 +186,26 @@ fn main() {
             .expect("Failed to configure thread pool");
     }
 
-    match list_subdirectories(&base_path) {
-        Ok(dirs) => {
-            if args.serial {
-                // 顺序执行
-                dirs.iter().for_each(|dir| {
-                    let full_path = Path::new(&base_path).join(dir.clone());
-                    fetch_repo(full_path, dir);
-                });
-            } else {
-                // 并行执行
-                dirs.par_iter().for_each(|dir| {
-                    let full_path = Path::new(&base_path).join(dir.clone());
-                    fetch_repo(full_path, dir);
-                });
-            }
+    let records = if let Some(manifest_path) = &args.manifest {
+        provision_from_manifest(&base_path, manifest_path, &command, &credentials, args.serial, args.format)
+    } else {
+        let repos = discover_repos(&base_path, args.depth);
+        let reporter = Reporter::new(repos.len(), args.format);
+        let run_one = |repo_path: &PathBuf| {
+            let label = display_label(&base_path, repo_path);
+            let child = reporter.child(label.clone());
+            let result = command.run(repo_path, child, &credentials);
+            reporter.repo_done();
+            Record::new(label, result)
+        };
+
+        if args.serial {
+            repos.iter().map(run_one).collect()
+        } else {
+            repos.par_iter().map(run_one).collect()
         }
-        Err(e) => eprintln!("错误: {}", e),
-    }
+    };
+
+    let exit_code = report::render(&records, args.format);
+    ExitCode::from(exit_code as u8)
 }
@@ -0,0 +1,49 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A list of repositories to provision, read from a TOML manifest passed via
+/// `--manifest`.
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+    #[serde(rename = "repo", default)]
+    pub repos: Vec<RepoEntry>,
+}
+
+/// A single manifest entry describing a repo to clone if it isn't already
+/// present under `base_path`.
+#[derive(Debug, Deserialize)]
+pub struct RepoEntry {
+    pub url: String,
+    pub dir: Option<PathBuf>,
+    pub branch: Option<String>,
+    pub revision: Option<String>,
+    #[serde(default)]
+    pub bare: bool,
+}
+
+impl RepoEntry {
+    /// Target directory for this entry: the explicit `dir` if given,
+    /// otherwise the repo name derived from the last path segment of `url`.
+    pub fn target_dir(&self, base_path: &Path) -> PathBuf {
+        match &self.dir {
+            Some(dir) => base_path.join(dir),
+            None => base_path.join(self.name_from_url()),
+        }
+    }
+
+    fn name_from_url(&self) -> String {
+        self.url
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or(&self.url)
+            .trim_end_matches(".git")
+            .to_string()
+    }
+}
+
+pub fn load(path: &Path) -> Result<Manifest, std::io::Error> {
+    let contents = fs::read_to_string(path)?;
+    toml::from_str(&contents).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
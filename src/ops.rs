@@ -0,0 +1,275 @@
+use crate::credentials;
+use crate::progress::ChildProgress;
+use crate::report::{Error, Outcome};
+use clap::Subcommand;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Per-repository operation selected via subcommand; `Fetch` is the default
+/// when none is given, preserving the tool's original behavior.
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// fetch remote refs for each repo (default)
+    Fetch,
+    /// fetch, then fast-forward the checked-out branch
+    Pull,
+    /// report ahead/behind counts and worktree dirtiness for each repo
+    Status,
+    /// switch each repo to a local or remote branch
+    Checkout { branch: String },
+}
+
+impl Command {
+    /// `progress` is a single child line reused across whatever phases this
+    /// operation has (e.g. ref-map preparation and pack reception); ops
+    /// that don't touch the network just ignore it and `credentials`.
+    pub fn run(
+        &self,
+        path: &Path,
+        progress: ChildProgress,
+        credentials: &credentials::Options,
+    ) -> Result<Outcome, Error> {
+        match self {
+            Command::Fetch => fetch_repo(path, progress, credentials),
+            Command::Pull => pull_repo(path, progress, credentials),
+            Command::Status => status_repo(path),
+            Command::Checkout { branch } => checkout_repo(path, branch),
+        }
+    }
+}
+
+fn open(path: &Path) -> Result<gix::Repository, Error> {
+    gix::open(path).map_err(|e| Error::NotAGitRepo(e.to_string()))
+}
+
+pub fn fetch_repo(path: &Path, progress: ChildProgress, credentials: &credentials::Options) -> Result<Outcome, Error> {
+    let repo = open(path)?;
+    let outcome = fetch(&repo, progress, credentials)?;
+    Ok(Outcome::Fetched {
+        objects: outcome.ref_map.remote_refs.len(),
+    })
+}
+
+fn pull_repo(path: &Path, progress: ChildProgress, credentials: &credentials::Options) -> Result<Outcome, Error> {
+    let repo = open(path)?;
+    let outcome = fetch(&repo, progress, credentials)?;
+
+    let local_id = repo
+        .head_id()
+        .map_err(|e| Error::Other(format!("failed to resolve HEAD, {e}")))?
+        .detach();
+
+    let local_branch = repo
+        .head_name()
+        .map_err(|e| Error::Other(format!("failed to read HEAD, {e}")))?
+        .ok_or_else(|| Error::Other("HEAD is detached, nothing to pull".to_string()))?;
+
+    let upstream_ref =
+        upstream_ref_name(&local_branch).ok_or_else(|| Error::Other("no upstream branch found".to_string()))?;
+
+    let upstream_id = outcome
+        .ref_map
+        .mappings
+        .iter()
+        .find(|mapping| mapping.local.as_ref().map(|name| name.to_string()) == Some(upstream_ref.clone()))
+        .and_then(|mapping| mapping.remote.as_id())
+        .ok_or_else(|| Error::Other(format!("{upstream_ref} not found in fetch result")))?
+        .to_owned();
+
+    fast_forward(&repo, local_id, upstream_id).map_err(|e| Error::Other(format!("fast-forward failed: {e}")))?;
+
+    Ok(Outcome::Pulled {
+        objects: outcome.ref_map.remote_refs.len(),
+    })
+}
+
+fn status_repo(path: &Path) -> Result<Outcome, Error> {
+    let repo = open(path)?;
+
+    let head_id = repo
+        .head_id()
+        .map_err(|e| Error::Other(format!("failed to resolve HEAD, {e}")))?;
+
+    let remote = repo
+        .find_default_remote(gix::remote::Direction::Fetch)
+        .transpose()
+        .map_err(|e| Error::RemoteLookupFailed(e.to_string()))?;
+
+    let dirty = repo
+        .is_dirty()
+        .map_err(|e| Error::Other(format!("failed to check worktree status, {e}")))?;
+
+    let upstream_id = remote
+        .and_then(|_| repo.head_name().ok().flatten())
+        .and_then(|name| upstream_ref_name(&name))
+        .and_then(|upstream| repo.find_reference(&upstream).ok())
+        .and_then(|mut r| r.peel_to_id().ok())
+        .map(|id| id.detach());
+
+    let Some(upstream_id) = upstream_id else {
+        return Ok(Outcome::Skipped {
+            reason: "no upstream branch".to_string(),
+        });
+    };
+
+    let (ahead, behind) = ahead_behind(&repo, head_id.detach(), upstream_id)
+        .map_err(|e| Error::Other(format!("failed to compute ahead/behind, {e}")))?;
+
+    Ok(Outcome::Status { ahead, behind, dirty })
+}
+
+fn checkout_repo(path: &Path, branch: &str) -> Result<Outcome, Error> {
+    let repo = open(path)?;
+
+    let local_ref = format!("refs/heads/{branch}");
+    let remote_ref = format!("refs/remotes/origin/{branch}");
+
+    let target_ref = if repo.find_reference(&local_ref).is_ok() {
+        local_ref
+    } else if let Ok(mut remote) = repo.find_reference(&remote_ref) {
+        let id = remote
+            .peel_to_id()
+            .map_err(|e| Error::Other(format!("failed to resolve remote branch, {e}")))?
+            .detach();
+        create_local_branch(&repo, &local_ref, id)
+            .map_err(|e| Error::Other(format!("failed to create local branch, {e}")))?;
+        local_ref
+    } else {
+        return Err(Error::Other(format!("branch {branch} does not exist")));
+    };
+
+    let target_id = repo
+        .find_reference(&target_ref)
+        .map_err(|e| Error::Other(format!("failed to resolve branch ref, {e}")))?
+        .peel_to_id()
+        .map_err(|e| Error::Other(format!("failed to resolve branch commit, {e}")))?
+        .detach();
+
+    set_head_symbolic(&repo, &target_ref).map_err(|e| Error::Other(format!("failed to switch branch, {e}")))?;
+    crate::worktree::checkout_commit(&repo, target_id)
+        .map_err(|e| Error::Other(format!("failed to update worktree, {e}")))?;
+
+    Ok(Outcome::CheckedOut {
+        branch: branch.to_string(),
+    })
+}
+
+/// Runs the `find_default_remote` / `connect` / `prepare_fetch` / `receive`
+/// chain shared by `fetch` and `pull`, reporting progress against `progress`
+/// across both the ref-map preparation and the pack reception, and
+/// authenticating via `credentials` if the remote asks for it.
+fn fetch(
+    repo: &gix::Repository,
+    mut progress: ChildProgress,
+    credentials: &credentials::Options,
+) -> Result<gix::remote::fetch::Outcome, Error> {
+    let remote = match repo.find_default_remote(gix::remote::Direction::Fetch) {
+        Some(Ok(remote)) => remote,
+        Some(Err(e)) => return Err(Error::RemoteLookupFailed(e.to_string())),
+        None => return Err(Error::NoRemoteConfigured),
+    };
+
+    let connection = remote
+        .connect(gix::remote::Direction::Fetch)
+        .map_err(|e| Error::ConnectFailed(e.to_string()))?;
+    let connection = credentials.configure(connection);
+
+    let prepared = connection
+        .prepare_fetch(&mut progress, gix::remote::ref_map::Options::default())
+        .map_err(|e| Error::FetchFailed(e.to_string()))?;
+
+    prepared
+        .receive(progress, &gix::interrupt::IS_INTERRUPTED)
+        .map_err(|e| Error::FetchFailed(e.to_string()))
+}
+
+fn upstream_ref_name(local_branch: &gix::refs::FullName) -> Option<String> {
+    let short = local_branch.as_bstr().strip_prefix(b"refs/heads/")?;
+    Some(format!("refs/remotes/origin/{}", String::from_utf8_lossy(short)))
+}
+
+/// Counts commits reachable from `local` but not `remote`, and vice versa.
+fn ahead_behind(
+    repo: &gix::Repository,
+    local: gix::ObjectId,
+    remote: gix::ObjectId,
+) -> Result<(usize, usize), gix::revision::walk::Error> {
+    let reachable_from = |start: gix::ObjectId| -> Result<HashSet<gix::ObjectId>, gix::revision::walk::Error> {
+        Ok(repo
+            .rev_walk([start])
+            .all()?
+            .filter_map(Result::ok)
+            .map(|info| info.id)
+            .collect())
+    };
+
+    let local_set = reachable_from(local)?;
+    let remote_set = reachable_from(remote)?;
+
+    let ahead = local_set.difference(&remote_set).count();
+    let behind = remote_set.difference(&local_set).count();
+    Ok((ahead, behind))
+}
+
+/// Fast-forwards the current branch's ref to `target`, then updates the
+/// worktree to match — but only once `local` is confirmed to be an ancestor
+/// of `target`. Without that check this would be a hard reset wearing a
+/// fast-forward's name: if the checked-out branch diverged from or is ahead
+/// of upstream, blindly moving the ref would silently discard local commits.
+fn fast_forward(
+    repo: &gix::Repository,
+    local: gix::ObjectId,
+    target: gix::ObjectId,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (ahead, _behind) = ahead_behind(repo, local, target)?;
+    if ahead > 0 {
+        return Err("refusing to update: local branch has commits not in upstream, this isn't a fast-forward".into());
+    }
+
+    let head_name = repo
+        .head_name()?
+        .ok_or("HEAD is detached, nothing to fast-forward")?;
+    repo.edit_reference(gix::refs::transaction::RefEdit {
+        change: gix::refs::transaction::Change::Update {
+            log: Default::default(),
+            expected: gix::refs::transaction::PreviousValue::Any,
+            new: gix::refs::Target::Object(target),
+        },
+        name: head_name,
+        deref: true,
+    })?;
+    crate::worktree::checkout_commit(repo, target)
+}
+
+fn create_local_branch(
+    repo: &gix::Repository,
+    local_ref: &str,
+    at: gix::ObjectId,
+) -> Result<(), Box<dyn std::error::Error>> {
+    repo.edit_reference(gix::refs::transaction::RefEdit {
+        change: gix::refs::transaction::Change::Update {
+            log: Default::default(),
+            expected: gix::refs::transaction::PreviousValue::MustNotExist,
+            new: gix::refs::Target::Object(at),
+        },
+        name: local_ref.try_into()?,
+        deref: false,
+    })?;
+    Ok(())
+}
+
+/// Points HEAD at `target_ref`. Callers are responsible for materializing
+/// the worktree for whatever commit `target_ref` now resolves to, since
+/// this only rewrites the symbolic ref.
+fn set_head_symbolic(repo: &gix::Repository, target_ref: &str) -> Result<(), Box<dyn std::error::Error>> {
+    repo.edit_reference(gix::refs::transaction::RefEdit {
+        change: gix::refs::transaction::Change::Update {
+            log: Default::default(),
+            expected: gix::refs::transaction::PreviousValue::Any,
+            new: gix::refs::Target::Symbolic(target_ref.try_into()?),
+        },
+        name: "HEAD".try_into()?,
+        deref: false,
+    })?;
+    Ok(())
+}
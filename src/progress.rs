@@ -0,0 +1,159 @@
+use crate::report::Format;
+use gix::progress::{Id, MessageLevel, Step, StepShared, Unit, UNKNOWN};
+use gix::{Count, NestedProgress, Progress};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Drives a live, terminal-rendered progress tree for a run: one child line
+/// per repo, updated concurrently by rayon workers, plus a top-level
+/// "N/total done" line advanced by whichever worker finishes a repo.
+///
+/// Falls back to `Quiet` (handing out silent `ChildProgress::Quiet` children)
+/// when stdout isn't a TTY or `--format json` was requested, since neither
+/// wants progress chatter interleaved with its output.
+pub enum Reporter {
+    Live {
+        root: Arc<prodash::tree::Root>,
+        top: prodash::tree::Item,
+        completed: AtomicUsize,
+        _render: prodash::render::line::JoinHandle,
+    },
+    Quiet,
+}
+
+impl Reporter {
+    pub fn new(total: usize, format: Format) -> Self {
+        if format == Format::Json || !std::io::stdout().is_terminal() {
+            return Reporter::Quiet;
+        }
+
+        let root = prodash::tree::Root::new();
+        let top = root.add_child("repos");
+        top.init(Some(total), gix::progress::count("done"));
+
+        let render = prodash::render::line(
+            std::io::stderr(),
+            Arc::downgrade(&root),
+            prodash::render::line::Options {
+                keep_running_if_progress_is_empty: true,
+                throughput: true,
+                ..prodash::render::line::Options::default()
+            }
+            .auto_configure(prodash::render::line::StreamKind::Stderr),
+        );
+
+        Reporter::Live {
+            root,
+            top,
+            completed: AtomicUsize::new(0),
+            _render: render,
+        }
+    }
+
+    /// Hands out a fresh progress line for one repo's work, labeled `name`.
+    pub fn child(&self, name: String) -> ChildProgress {
+        match self {
+            Reporter::Live { root, .. } => ChildProgress::Live(root.add_child(name)),
+            Reporter::Quiet => ChildProgress::Quiet,
+        }
+    }
+
+    /// Call once a repo's operation has finished, successfully or not, to
+    /// advance the top-level "N/total done" line.
+    pub fn repo_done(&self) {
+        if let Reporter::Live { top, completed, .. } = self {
+            let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
+            top.set(done);
+        }
+    }
+}
+
+/// A per-repo progress line, or a silent no-op when running quiet. `Live`
+/// wraps a `prodash::tree::Item`, which isn't `Clone`, so a phase that needs
+/// its own line (e.g. a clone's checkout phase, alongside its fetch) calls
+/// `add_child` on this one to get a nested one, rather than cloning it.
+pub enum ChildProgress {
+    Live(prodash::tree::Item),
+    Quiet,
+}
+
+impl Count for ChildProgress {
+    fn set(&self, step: Step) {
+        if let ChildProgress::Live(p) = self {
+            Count::set(p, step);
+        }
+    }
+
+    fn step(&self) -> Step {
+        match self {
+            ChildProgress::Live(p) => Count::step(p),
+            ChildProgress::Quiet => 0,
+        }
+    }
+
+    fn inc_by(&self, step: Step) {
+        if let ChildProgress::Live(p) = self {
+            Count::inc_by(p, step);
+        }
+    }
+
+    fn counter(&self) -> StepShared {
+        match self {
+            ChildProgress::Live(p) => Count::counter(p),
+            ChildProgress::Quiet => Arc::new(AtomicUsize::default()),
+        }
+    }
+}
+
+impl Progress for ChildProgress {
+    fn init(&mut self, max: Option<Step>, unit: Option<Unit>) {
+        if let ChildProgress::Live(p) = self {
+            p.init(max, unit);
+        }
+    }
+
+    fn set_name(&mut self, name: String) {
+        if let ChildProgress::Live(p) = self {
+            p.set_name(name);
+        }
+    }
+
+    fn name(&self) -> Option<String> {
+        match self {
+            ChildProgress::Live(p) => p.name(),
+            ChildProgress::Quiet => None,
+        }
+    }
+
+    fn id(&self) -> Id {
+        match self {
+            ChildProgress::Live(p) => p.id(),
+            ChildProgress::Quiet => UNKNOWN,
+        }
+    }
+
+    fn message(&self, level: MessageLevel, message: String) {
+        if let ChildProgress::Live(p) = self {
+            p.message(level, message);
+        }
+    }
+}
+
+impl NestedProgress for ChildProgress {
+    type SubProgress = ChildProgress;
+
+    fn add_child(&mut self, name: impl Into<String>) -> Self::SubProgress {
+        match self {
+            ChildProgress::Live(p) => ChildProgress::Live(p.add_child(name)),
+            ChildProgress::Quiet => ChildProgress::Quiet,
+        }
+    }
+
+    fn add_child_with_id(&mut self, name: impl Into<String>, id: Id) -> Self::SubProgress {
+        match self {
+            ChildProgress::Live(p) => ChildProgress::Live(p.add_child_with_id(name, id)),
+            ChildProgress::Quiet => ChildProgress::Quiet,
+        }
+    }
+}
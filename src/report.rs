@@ -0,0 +1,267 @@
+use std::fmt;
+
+/// Everything that can go wrong while running an operation against a single
+/// repo. Carries enough detail to render either a human-readable line or a
+/// `--format json` record.
+#[derive(Debug)]
+pub enum Error {
+    NotAGitRepo(String),
+    NoRemoteConfigured,
+    RemoteLookupFailed(String),
+    ConnectFailed(String),
+    FetchFailed(String),
+    Other(String),
+}
+
+impl Error {
+    /// Stable, machine-readable name for `--format json` output.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::NotAGitRepo(_) => "not_a_git_repo",
+            Error::NoRemoteConfigured => "no_remote_configured",
+            Error::RemoteLookupFailed(_) => "remote_lookup_failed",
+            Error::ConnectFailed(_) => "connect_failed",
+            Error::FetchFailed(_) => "fetch_failed",
+            Error::Other(_) => "other",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotAGitRepo(e) => write!(f, "not a git repo: {e}"),
+            Error::NoRemoteConfigured => write!(f, "no remote configured"),
+            Error::RemoteLookupFailed(e) => write!(f, "failed to look up remote: {e}"),
+            Error::ConnectFailed(e) => write!(f, "failed to connect: {e}"),
+            Error::FetchFailed(e) => write!(f, "fetch failed: {e}"),
+            Error::Other(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// What an operation accomplished for one repo, on success.
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    Fetched { objects: usize },
+    Pulled { objects: usize },
+    Cloned { objects: usize },
+    Status { ahead: usize, behind: usize, dirty: bool },
+    CheckedOut { branch: String },
+    Skipped { reason: String },
+}
+
+impl Outcome {
+    fn object_count(&self) -> usize {
+        match self {
+            Outcome::Fetched { objects } | Outcome::Pulled { objects } | Outcome::Cloned { objects } => *objects,
+            Outcome::Status { .. } | Outcome::CheckedOut { .. } | Outcome::Skipped { .. } => 0,
+        }
+    }
+}
+
+/// The result of running an operation against one repo, labeled with its
+/// display path.
+pub struct Record {
+    pub label: String,
+    pub result: Result<Outcome, Error>,
+}
+
+impl Record {
+    pub fn new(label: String, result: Result<Outcome, Error>) -> Self {
+        Record { label, result }
+    }
+
+    fn text_line(&self) -> String {
+        match &self.result {
+            Ok(Outcome::Fetched { objects }) => format!("{}: fetched, received {} objects", self.label, objects),
+            Ok(Outcome::Pulled { objects }) => {
+                format!("{}: pulled and fast-forwarded, received {} objects", self.label, objects)
+            }
+            Ok(Outcome::Cloned { objects }) => format!("{}: cloned, received {} objects", self.label, objects),
+            Ok(Outcome::Status { ahead, behind, dirty }) => format!(
+                "{}: {} ahead, {} behind{}",
+                self.label,
+                ahead,
+                behind,
+                if *dirty { ", worktree has uncommitted changes" } else { "" }
+            ),
+            Ok(Outcome::CheckedOut { branch }) => format!("{}: switched to {}", self.label, branch),
+            Ok(Outcome::Skipped { reason }) => format!("{}: {}", self.label, reason),
+            Err(e) => format!("{}: {}", self.label, e),
+        }
+    }
+}
+
+/// Counts and totals across a run, tallied for the end-of-run summary.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub objects_received: usize,
+}
+
+impl Summary {
+    fn tally(records: &[Record]) -> Self {
+        let mut summary = Summary::default();
+        for record in records {
+            match &record.result {
+                Ok(Outcome::Skipped { .. }) => summary.skipped += 1,
+                Ok(outcome) => {
+                    summary.succeeded += 1;
+                    summary.objects_received += outcome.object_count();
+                }
+                Err(_) => summary.failed += 1,
+            }
+        }
+        summary
+    }
+}
+
+/// Renders `records` in the requested format and returns the process exit
+/// code (non-zero if any repo failed).
+pub fn render(records: &[Record], format: Format) -> i32 {
+    match format {
+        Format::Text => render_text(records),
+        Format::Json => render_json(records),
+    }
+}
+
+fn render_text(records: &[Record]) -> i32 {
+    for record in records {
+        match &record.result {
+            Ok(_) => println!("{}", record.text_line()),
+            Err(_) => eprintln!("{}", record.text_line()),
+        }
+    }
+
+    let summary = Summary::tally(records);
+    println!(
+        "summary: {} succeeded, {} failed, {} skipped, {} objects received",
+        summary.succeeded, summary.failed, summary.skipped, summary.objects_received
+    );
+
+    if summary.failed > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+fn render_json(records: &[Record]) -> i32 {
+    let mut failed = 0;
+
+    for record in records {
+        let line = match &record.result {
+            Ok(Outcome::Fetched { objects }) => {
+                format!(
+                    r#"{{"path":{},"status":"fetched","objects":{}}}"#,
+                    json_string(&record.label),
+                    objects
+                )
+            }
+            Ok(Outcome::Pulled { objects }) => {
+                format!(
+                    r#"{{"path":{},"status":"pulled","objects":{}}}"#,
+                    json_string(&record.label),
+                    objects
+                )
+            }
+            Ok(Outcome::Cloned { objects }) => {
+                format!(
+                    r#"{{"path":{},"status":"cloned","objects":{}}}"#,
+                    json_string(&record.label),
+                    objects
+                )
+            }
+            Ok(Outcome::Status { ahead, behind, dirty }) => {
+                format!(
+                    r#"{{"path":{},"status":"status","ahead":{},"behind":{},"dirty":{}}}"#,
+                    json_string(&record.label),
+                    ahead,
+                    behind,
+                    dirty
+                )
+            }
+            Ok(Outcome::CheckedOut { branch }) => {
+                format!(
+                    r#"{{"path":{},"status":"checked_out","branch":{}}}"#,
+                    json_string(&record.label),
+                    json_string(branch)
+                )
+            }
+            Ok(Outcome::Skipped { reason }) => {
+                format!(
+                    r#"{{"path":{},"status":"skipped","reason":{}}}"#,
+                    json_string(&record.label),
+                    json_string(reason)
+                )
+            }
+            Err(e) => {
+                failed += 1;
+                format!(
+                    r#"{{"path":{},"status":"failed","error_kind":"{}","error":{}}}"#,
+                    json_string(&record.label),
+                    e.kind(),
+                    json_string(&e.to_string())
+                )
+            }
+        };
+        println!("{line}");
+    }
+
+    if failed > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::json_string;
+
+    #[test]
+    fn escapes_quotes_backslashes_and_newlines() {
+        assert_eq!(json_string(r#"a"b\c"#), r#""a\"b\\c""#);
+        assert_eq!(json_string("line1\nline2"), r#""line1\nline2""#);
+    }
+
+    #[test]
+    fn escapes_other_control_characters() {
+        assert_eq!(json_string("a\rb"), r#""a\rb""#);
+        assert_eq!(json_string("a\tb"), r#""a\tb""#);
+        assert_eq!(json_string("a\u{1}b"), "\"a\\u0001b\"");
+    }
+}
+
+/// Output format for the end-of-run report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Format {
+    /// human-readable lines plus a summary (default)
+    #[default]
+    Text,
+    /// one JSON record per repo, for consumption by other tools
+    Json,
+}
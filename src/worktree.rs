@@ -0,0 +1,25 @@
+/// Builds an index from `commit`'s tree and checks it out into `repo`'s
+/// worktree, writing the rebuilt index back to disk. Shared by `ops`'s
+/// `pull`/`checkout` subcommands and `clone`'s `revision` pin, since both
+/// need to move the worktree to a commit that isn't the one currently on
+/// disk, not just re-stamp whatever index already happens to be there.
+pub fn checkout_commit(repo: &gix::Repository, commit: gix::ObjectId) -> Result<(), Box<dyn std::error::Error>> {
+    let work_dir = repo.workdir().ok_or("repo has no worktree to update")?;
+    let tree_id = repo.find_commit(commit)?.tree_id()?.detach();
+
+    let mut index = repo.index_from_tree(&tree_id)?;
+    gix::worktree::state::checkout(
+        &mut index,
+        work_dir,
+        repo.objects.clone(),
+        &gix::progress::Discard,
+        &gix::progress::Discard,
+        &gix::interrupt::IS_INTERRUPTED,
+        gix::worktree::state::checkout::Options {
+            overwrite_existing: true,
+            ..Default::default()
+        },
+    )?;
+    index.write(gix::index::write::Options::default())?;
+    Ok(())
+}